@@ -0,0 +1,171 @@
+// Copyright (C) 2023 Checkmk GmbH - License: GNU General Public License v2
+// This file is part of Checkmk (https://checkmk.com). It is subject to the terms and
+// conditions defined in the file COPYING, which is part of this source code package.
+
+use crate::checking::{CheckResult, State};
+use crate::cli::Cli;
+use crate::http;
+use crate::runner;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+pub struct BatchResult {
+    pub url: String,
+    pub checks: Vec<CheckResult>,
+}
+
+pub struct BatchSummary {
+    pub results: Vec<BatchResult>,
+    pub ok: usize,
+    pub warn: usize,
+    pub crit: usize,
+    pub unknown: usize,
+    pub worst: State,
+}
+
+/// Runs `collect_checks` against every URL in `urls` concurrently, bounded by
+/// `args.max_concurrency` permits on a single shared client, and aggregates the per-URL states.
+pub async fn run_batch(urls: Vec<String>, args: Arc<Cli>) -> BatchSummary {
+    let client = match http::build_client(
+        args.timeout,
+        args.onredirect.clone(),
+        args.max_redirs,
+        args.force_ip_version.clone(),
+    ) {
+        Ok(client) => client,
+        Err(err) => {
+            return summarize(
+                urls.into_iter()
+                    .map(|url| BatchResult {
+                        url,
+                        checks: vec![CheckResult::new(
+                            State::Unknown,
+                            format!("Error building HTTP client: {err}"),
+                        )],
+                    })
+                    .collect(),
+            );
+        }
+    };
+
+    let semaphore = Arc::new(Semaphore::new(args.max_concurrency.max(1)));
+
+    let mut handles = Vec::with_capacity(urls.len());
+    for url in urls {
+        let semaphore = semaphore.clone();
+        let args = args.clone();
+        let client = client.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let checks = runner::collect_checks(url.clone(), &args, &client).await;
+            BatchResult { url, checks }
+        }));
+    }
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        match handle.await {
+            Ok(result) => results.push(result),
+            Err(_) => results.push(BatchResult {
+                url: "<unknown>".to_string(),
+                checks: vec![CheckResult::new(
+                    State::Unknown,
+                    "Check task panicked".to_string(),
+                )],
+            }),
+        }
+    }
+
+    summarize(results)
+}
+
+/// Orders states by how bad they are for aggregation purposes, which is not the same as
+/// their Nagios exit-code order (`Unknown` outranks `Crit` numerically but not severity-wise).
+fn severity_rank(state: State) -> u8 {
+    match state {
+        State::Ok => 0,
+        State::Warn => 1,
+        State::Unknown => 2,
+        State::Crit => 3,
+    }
+}
+
+fn summarize(results: Vec<BatchResult>) -> BatchSummary {
+    let mut ok = 0;
+    let mut warn = 0;
+    let mut crit = 0;
+    let mut unknown = 0;
+    let mut worst = State::Ok;
+
+    for result in &results {
+        for check in &result.checks {
+            match check.state {
+                State::Ok => ok += 1,
+                State::Warn => warn += 1,
+                State::Crit => crit += 1,
+                State::Unknown => unknown += 1,
+            }
+            if severity_rank(check.state) > severity_rank(worst) {
+                worst = check.state;
+            }
+        }
+    }
+
+    BatchSummary {
+        results,
+        ok,
+        warn,
+        crit,
+        unknown,
+        worst,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, states: Vec<State>) -> BatchResult {
+        BatchResult {
+            url: url.to_string(),
+            checks: states
+                .into_iter()
+                .map(|state| CheckResult::new(state, "test".to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn severity_rank_orders_crit_above_unknown_above_warn_above_ok() {
+        assert!(severity_rank(State::Crit) > severity_rank(State::Unknown));
+        assert!(severity_rank(State::Unknown) > severity_rank(State::Warn));
+        assert!(severity_rank(State::Warn) > severity_rank(State::Ok));
+    }
+
+    #[test]
+    fn summarize_counts_each_state() {
+        let summary = summarize(vec![
+            result("a", vec![State::Ok, State::Warn]),
+            result("b", vec![State::Crit, State::Unknown]),
+        ]);
+        assert_eq!(summary.ok, 1);
+        assert_eq!(summary.warn, 1);
+        assert_eq!(summary.crit, 1);
+        assert_eq!(summary.unknown, 1);
+    }
+
+    #[test]
+    fn summarize_worst_is_crit_even_behind_unknown() {
+        let summary = summarize(vec![result("a", vec![State::Unknown, State::Crit])]);
+        assert_eq!(summary.worst, State::Crit);
+    }
+
+    #[test]
+    fn summarize_worst_defaults_to_ok_when_empty() {
+        let summary = summarize(vec![]);
+        assert_eq!(summary.worst, State::Ok);
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (C) 2023 Checkmk GmbH - License: GNU General Public License v2
+// This file is part of Checkmk (https://checkmk.com). It is subject to the terms and
+// conditions defined in the file COPYING, which is part of this source code package.
+
+use crate::cli::OnRedirect;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use reqwest::{StatusCode, Version};
+use std::collections::HashSet;
+use std::time::Duration;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
+use x509_parser::time::ASN1Time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum State {
+    Ok = 0,
+    Warn = 1,
+    Crit = 2,
+    Unknown = 3,
+}
+
+#[derive(Debug)]
+pub struct CheckResult {
+    pub state: State,
+    pub summary: String,
+}
+
+impl CheckResult {
+    pub fn new(state: State, summary: impl Into<String>) -> Self {
+        Self {
+            state,
+            summary: summary.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Bounds<T> {
+    None,
+    Lower(T),
+    LowerUpper(T, T),
+}
+
+#[derive(Clone, Copy)]
+pub enum Limits<T> {
+    None,
+    Warn(T),
+    WarnCrit(T, T),
+}
+
+pub fn check_status(
+    status: StatusCode,
+    _version: Version,
+    onredirect: OnRedirect,
+    expected_status: Option<&HashSet<u16>>,
+    attempts: u32,
+) -> Option<CheckResult> {
+    let suffix = if attempts > 1 {
+        format!(" (after {attempts} attempts)")
+    } else {
+        String::new()
+    };
+
+    if let Some(expected) = expected_status {
+        let state = if expected.contains(&status.as_u16()) {
+            State::Ok
+        } else {
+            State::Crit
+        };
+        return Some(CheckResult::new(state, format!("HTTP {status}{suffix}")));
+    }
+
+    if status.is_redirection() {
+        return Some(match onredirect {
+            OnRedirect::Warn => CheckResult::new(State::Warn, format!("HTTP {status}{suffix}")),
+            OnRedirect::Crit => CheckResult::new(State::Crit, format!("HTTP {status}{suffix}")),
+            OnRedirect::Unknown => {
+                CheckResult::new(State::Unknown, format!("HTTP {status}{suffix}"))
+            }
+            _ => CheckResult::new(State::Ok, format!("HTTP {status}{suffix}")),
+        });
+    }
+
+    if status.is_success() {
+        Some(CheckResult::new(
+            State::Ok,
+            format!("HTTP {status}{suffix}"),
+        ))
+    } else {
+        Some(CheckResult::new(
+            State::Crit,
+            format!("HTTP {status}{suffix}"),
+        ))
+    }
+}
+
+pub fn check_body(body: Option<String>, bounds: Bounds<usize>) -> Option<CheckResult> {
+    let body = body?;
+    let size = body.len();
+
+    let state = match bounds {
+        Bounds::None => State::Ok,
+        Bounds::Lower(min) if size < min => State::Warn,
+        Bounds::LowerUpper(min, max) if size < min || size > max => State::Warn,
+        _ => State::Ok,
+    };
+
+    Some(CheckResult::new(state, format!("Page size: {size} bytes")))
+}
+
+pub fn check_content(
+    body: Option<&str>,
+    expected_string: Option<&str>,
+    regex: Option<&Regex>,
+    invert_regex: bool,
+) -> Option<CheckResult> {
+    if expected_string.is_none() && regex.is_none() {
+        return None;
+    }
+    let body = body?;
+
+    if let Some(expected) = expected_string {
+        if !body.contains(expected) {
+            return Some(CheckResult::new(
+                State::Crit,
+                format!("String \"{expected}\" not found in response body"),
+            ));
+        }
+    }
+
+    if let Some(pattern) = regex {
+        let found = pattern.is_match(body);
+        if found == invert_regex {
+            let verb = if invert_regex { "found" } else { "not found" };
+            return Some(CheckResult::new(
+                State::Crit,
+                format!("Pattern \"{}\" {verb} in response body", pattern.as_str()),
+            ));
+        }
+    }
+
+    Some(CheckResult::new(State::Ok, "Content matches".to_string()))
+}
+
+pub fn check_response_time(elapsed: Duration, limits: Limits<Duration>) -> Option<CheckResult> {
+    let state = match limits {
+        Limits::None => State::Ok,
+        Limits::Warn(warn) if elapsed >= warn => State::Warn,
+        Limits::WarnCrit(_, crit) if elapsed >= crit => State::Crit,
+        Limits::WarnCrit(warn, _) if elapsed >= warn => State::Warn,
+        _ => State::Ok,
+    };
+
+    Some(CheckResult::new(
+        state,
+        format!("Response time: {:.3}s", elapsed.as_secs_f64()),
+    ))
+}
+
+/// Classifies the number of days left until certificate expiry against warn/crit levels.
+/// Pulled out of `check_certificate` so the threshold logic can be tested without a real
+/// certificate.
+fn classify_days_remaining(remaining_days: i64, levels: Limits<i64>) -> State {
+    match levels {
+        Limits::None => State::Ok,
+        Limits::Warn(warn) if remaining_days <= warn => State::Warn,
+        Limits::WarnCrit(_, crit) if remaining_days <= crit => State::Crit,
+        Limits::WarnCrit(warn, _) if remaining_days <= warn => State::Warn,
+        _ => State::Ok,
+    }
+}
+
+pub fn check_certificate(cert_der: &[u8], levels: Limits<i64>) -> Option<CheckResult> {
+    let (_, cert) = X509Certificate::from_der(cert_der).ok()?;
+    let not_after = cert.validity().not_after;
+    let remaining_days = (not_after.timestamp() - ASN1Time::now().timestamp()) / 86400;
+    let state = classify_days_remaining(remaining_days, levels);
+
+    Some(CheckResult::new(
+        state,
+        format!("Certificate expires in {remaining_days} days"),
+    ))
+}
+
+pub fn check_document_age(headers: &HeaderMap, max_age: Option<u64>) -> Option<CheckResult> {
+    let max_age = max_age?;
+
+    let date = headers
+        .get(reqwest::header::LAST_MODIFIED)
+        .or_else(|| headers.get(reqwest::header::DATE))?
+        .to_str()
+        .ok()?;
+
+    let document_time = httpdate::parse_http_date(date).ok()?;
+    let age = document_time.elapsed().unwrap_or_default().as_secs();
+
+    if age > max_age {
+        Some(CheckResult::new(
+            State::Crit,
+            format!("Document age is {age}s, expected at most {max_age}s"),
+        ))
+    } else {
+        Some(CheckResult::new(State::Ok, format!("Document age: {age}s")))
+    }
+}
+
+/// The subset of `Cache-Control` directives relevant to monitoring caching behavior.
+struct CacheControl {
+    max_age: Option<u64>,
+    no_cache: bool,
+    no_store: bool,
+    must_revalidate: bool,
+}
+
+impl CacheControl {
+    fn parse(value: &str) -> Self {
+        let mut cache_control = Self {
+            max_age: None,
+            no_cache: false,
+            no_store: false,
+            must_revalidate: false,
+        };
+
+        for directive in value.split(',') {
+            let directive = directive.trim();
+            if let Some(value) = directive.strip_prefix("max-age=") {
+                cache_control.max_age = value.parse().ok();
+            } else {
+                match directive {
+                    "no-cache" => cache_control.no_cache = true,
+                    "no-store" => cache_control.no_store = true,
+                    "must-revalidate" => cache_control.must_revalidate = true,
+                    _ => {}
+                }
+            }
+        }
+
+        cache_control
+    }
+}
+
+pub fn check_cache(headers: &HeaderMap, min_max_age: Option<u64>) -> Option<CheckResult> {
+    let min_max_age = min_max_age?;
+
+    let Some(cache_control) = headers
+        .get(reqwest::header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .map(CacheControl::parse)
+    else {
+        return Some(CheckResult::new(
+            State::Warn,
+            "No Cache-Control header present".to_string(),
+        ));
+    };
+
+    if cache_control.no_store {
+        return Some(CheckResult::new(
+            State::Warn,
+            "Cache-Control: no-store".to_string(),
+        ));
+    }
+
+    match cache_control.max_age {
+        None => Some(CheckResult::new(
+            State::Warn,
+            "Cache-Control has no max-age".to_string(),
+        )),
+        Some(max_age) if max_age < min_max_age => Some(CheckResult::new(
+            State::Warn,
+            format!("Cache-Control max-age is {max_age}s, expected at least {min_max_age}s"),
+        )),
+        Some(max_age) => Some(CheckResult::new(
+            State::Ok,
+            format!(
+                "Cache-Control max-age: {max_age}s{}{}",
+                if cache_control.no_cache {
+                    ", no-cache"
+                } else {
+                    ""
+                },
+                if cache_control.must_revalidate {
+                    ", must-revalidate"
+                } else {
+                    ""
+                },
+            ),
+        )),
+    }
+}
+
+pub fn check_not_modified(status: StatusCode) -> Option<CheckResult> {
+    if status == StatusCode::NOT_MODIFIED {
+        Some(CheckResult::new(
+            State::Ok,
+            "HTTP 304 Not Modified".to_string(),
+        ))
+    } else {
+        Some(CheckResult::new(
+            State::Crit,
+            format!("Expected HTTP 304 Not Modified, got HTTP {status}"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_days_remaining_no_levels_is_always_ok() {
+        assert_eq!(classify_days_remaining(-5, Limits::None), State::Ok);
+        assert_eq!(classify_days_remaining(9000, Limits::None), State::Ok);
+    }
+
+    #[test]
+    fn classify_days_remaining_warn_only() {
+        assert_eq!(classify_days_remaining(30, Limits::Warn(14)), State::Ok);
+        assert_eq!(classify_days_remaining(14, Limits::Warn(14)), State::Warn);
+        assert_eq!(classify_days_remaining(1, Limits::Warn(14)), State::Warn);
+        assert_eq!(classify_days_remaining(-1, Limits::Warn(14)), State::Warn);
+    }
+
+    #[test]
+    fn classify_days_remaining_warn_and_crit() {
+        let levels = Limits::WarnCrit(30, 14);
+        assert_eq!(classify_days_remaining(31, levels), State::Ok);
+        assert_eq!(classify_days_remaining(30, levels), State::Warn);
+        assert_eq!(classify_days_remaining(15, levels), State::Warn);
+        assert_eq!(classify_days_remaining(14, levels), State::Crit);
+        assert_eq!(classify_days_remaining(-10, levels), State::Crit);
+    }
+
+    #[test]
+    fn check_content_no_assertions_is_noop() {
+        assert!(check_content(Some("hello"), None, None, false).is_none());
+    }
+
+    #[test]
+    fn check_content_string_present() {
+        let result = check_content(Some("hello world"), Some("world"), None, false).unwrap();
+        assert_eq!(result.state, State::Ok);
+    }
+
+    #[test]
+    fn check_content_string_absent() {
+        let result = check_content(Some("hello world"), Some("missing"), None, false).unwrap();
+        assert_eq!(result.state, State::Crit);
+    }
+
+    #[test]
+    fn check_content_regex_match() {
+        let re = Regex::new("wo.ld").unwrap();
+        let result = check_content(Some("hello world"), None, Some(&re), false).unwrap();
+        assert_eq!(result.state, State::Ok);
+    }
+
+    #[test]
+    fn check_content_regex_no_match() {
+        let re = Regex::new("missing").unwrap();
+        let result = check_content(Some("hello world"), None, Some(&re), false).unwrap();
+        assert_eq!(result.state, State::Crit);
+    }
+
+    #[test]
+    fn check_content_inverted_regex_found_is_crit() {
+        let re = Regex::new("wo.ld").unwrap();
+        let result = check_content(Some("hello world"), None, Some(&re), true).unwrap();
+        assert_eq!(result.state, State::Crit);
+    }
+
+    #[test]
+    fn check_content_inverted_regex_not_found_is_ok() {
+        let re = Regex::new("missing").unwrap();
+        let result = check_content(Some("hello world"), None, Some(&re), true).unwrap();
+        assert_eq!(result.state, State::Ok);
+    }
+
+    #[test]
+    fn cache_control_parse_max_age() {
+        let cc = CacheControl::parse("max-age=10");
+        assert_eq!(cc.max_age, Some(10));
+        assert!(!cc.no_cache);
+        assert!(!cc.no_store);
+        assert!(!cc.must_revalidate);
+    }
+
+    #[test]
+    fn cache_control_parse_no_store_with_max_age() {
+        let cc = CacheControl::parse("no-store, max-age=10");
+        assert_eq!(cc.max_age, Some(10));
+        assert!(cc.no_store);
+    }
+
+    #[test]
+    fn cache_control_parse_all_directives() {
+        let cc = CacheControl::parse("no-cache, no-store, must-revalidate, max-age=30");
+        assert_eq!(cc.max_age, Some(30));
+        assert!(cc.no_cache);
+        assert!(cc.no_store);
+        assert!(cc.must_revalidate);
+    }
+
+    #[test]
+    fn cache_control_parse_empty() {
+        let cc = CacheControl::parse("");
+        assert_eq!(cc.max_age, None);
+        assert!(!cc.no_cache);
+        assert!(!cc.no_store);
+        assert!(!cc.must_revalidate);
+    }
+
+    #[test]
+    fn cache_control_parse_ignores_unknown_directives() {
+        let cc = CacheControl::parse("private, max-age=5, stale-while-revalidate=60");
+        assert_eq!(cc.max_age, Some(5));
+        assert!(!cc.no_cache);
+    }
+
+    fn headers_with_cache_control(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::CACHE_CONTROL, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn check_cache_disabled_without_min_max_age() {
+        assert!(check_cache(&HeaderMap::new(), None).is_none());
+    }
+
+    #[test]
+    fn check_cache_missing_header_is_warn() {
+        let result = check_cache(&HeaderMap::new(), Some(60)).unwrap();
+        assert_eq!(result.state, State::Warn);
+    }
+
+    #[test]
+    fn check_cache_no_store_is_warn() {
+        let headers = headers_with_cache_control("no-store, max-age=120");
+        let result = check_cache(&headers, Some(60)).unwrap();
+        assert_eq!(result.state, State::Warn);
+    }
+
+    #[test]
+    fn check_cache_no_max_age_is_warn() {
+        let headers = headers_with_cache_control("no-cache");
+        let result = check_cache(&headers, Some(60)).unwrap();
+        assert_eq!(result.state, State::Warn);
+    }
+
+    #[test]
+    fn check_cache_low_max_age_is_warn() {
+        let headers = headers_with_cache_control("max-age=30");
+        let result = check_cache(&headers, Some(60)).unwrap();
+        assert_eq!(result.state, State::Warn);
+    }
+
+    #[test]
+    fn check_cache_sufficient_max_age_is_ok() {
+        let headers = headers_with_cache_control("max-age=120");
+        let result = check_cache(&headers, Some(60)).unwrap();
+        assert_eq!(result.state, State::Ok);
+    }
+}
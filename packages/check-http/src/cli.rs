@@ -0,0 +1,284 @@
+// Copyright (C) 2023 Checkmk GmbH - License: GNU General Public License v2
+// This file is part of Checkmk (https://checkmk.com). It is subject to the terms and
+// conditions defined in the file COPYING, which is part of this source code package.
+
+use clap::{ArgGroup, Parser, ValueEnum};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(
+    about = "check_http, reimplemented in Rust",
+    group(ArgGroup::new("auth_pw").args(["auth_pw_plain", "auth_pwstore"])),
+)]
+pub struct Cli {
+    /// URL to check. Repeat `--url` to check several endpoints in batch mode.
+    #[arg(short = 'u', long = "url", required_unless_present = "url_file")]
+    pub urls: Vec<String>,
+
+    /// Path to a file of URLs (one per line) to check in batch mode
+    #[arg(long)]
+    pub url_file: Option<PathBuf>,
+
+    /// Maximum number of URLs to check concurrently in batch mode
+    #[arg(long, default_value_t = 128)]
+    pub max_concurrency: usize,
+
+    /// HTTP method
+    #[arg(long, value_enum, default_value_t = Method::Get)]
+    pub method: Method,
+
+    /// User-Agent header to send
+    #[arg(long)]
+    pub user_agent: Option<String>,
+
+    /// Additional headers, in the form "Name: value"
+    #[arg(long = "header")]
+    pub headers: Vec<String>,
+
+    /// Seconds before the connection times out
+    #[arg(long, default_value_t = 10)]
+    pub timeout: u64,
+
+    /// Username for basic auth
+    #[arg(long)]
+    pub auth_user: Option<String>,
+
+    #[command(flatten)]
+    pub auth_pw: AuthPw,
+
+    /// How to treat redirects
+    #[arg(long, value_enum, default_value_t = OnRedirect::Ok)]
+    pub onredirect: OnRedirect,
+
+    /// Maximum number of redirects to follow
+    #[arg(long, default_value_t = 15)]
+    pub max_redirs: u8,
+
+    /// Force the given IP version
+    #[arg(long, value_enum)]
+    pub force_ip_version: Option<ForceIP>,
+
+    /// Don't download the response body
+    #[arg(long)]
+    pub without_body: bool,
+
+    /// Minimum (and optionally maximum) expected page size in bytes, as "min" or "min,max"
+    #[arg(long, value_parser = parse_levels)]
+    pub page_size: Option<(usize, Option<usize>)>,
+
+    /// Warning (and optionally critical) response time thresholds in seconds, as "warn" or "warn,crit"
+    #[arg(long, value_parser = parse_float_levels)]
+    pub response_time_levels: Option<(f64, Option<f64>)>,
+
+    /// Critical threshold in seconds for the age of the document, read from the response headers
+    #[arg(long)]
+    pub document_age_levels: Option<u64>,
+
+    /// Warning (and optionally critical) thresholds, in days, for TLS certificate expiry.
+    /// When set, only the certificate check is performed.
+    #[arg(long, value_parser = parse_u64_levels)]
+    pub certificate: Option<(u64, Option<u64>)>,
+
+    /// Number of times to retry the request on a timeout, connection error, or 5xx response
+    #[arg(long, default_value_t = 0)]
+    pub max_retries: u32,
+
+    /// Seconds to wait between retries
+    #[arg(long, default_value_t = 1)]
+    pub retry_wait: u64,
+
+    /// HTTP status codes to accept as OK, e.g. "200..=204,301,429". Individual codes and
+    /// inclusive ranges may be combined, comma-separated. Overrides the default status
+    /// evaluation (2xx OK, 3xx as set by `--onredirect`, everything else critical).
+    #[arg(long = "expected-status", visible_alias = "accept", value_parser = parse_status_set)]
+    pub expected_status: Option<HashSet<u16>>,
+
+    /// Require this substring to be present in the response body
+    #[arg(long)]
+    pub string: Option<String>,
+
+    /// Require this regular expression to match the response body
+    #[arg(long)]
+    pub regex: Option<String>,
+
+    /// Invert the `--regex` match: critical if the pattern IS found
+    #[arg(long, requires = "regex")]
+    pub invert_regex: bool,
+
+    /// Minimum acceptable `Cache-Control: max-age` in seconds. Also enables warning on
+    /// `no-store` or a missing `Cache-Control` header.
+    #[arg(long)]
+    pub cache_max_age: Option<u64>,
+
+    /// Send `If-None-Match: <etag>` and expect a 304 Not Modified response
+    #[arg(long)]
+    pub if_none_match: Option<String>,
+
+    /// Send `If-Modified-Since: <http-date>` and expect a 304 Not Modified response
+    #[arg(long)]
+    pub if_modified_since: Option<String>,
+}
+
+impl Cli {
+    /// Combines URLs passed via repeated `--url` flags with any listed, one per line, in
+    /// `--url-file`. Blank lines and `#`-comments in the file are skipped.
+    pub fn resolve_urls(&self) -> std::io::Result<Vec<String>> {
+        let mut urls = self.urls.clone();
+
+        if let Some(path) = &self.url_file {
+            let contents = std::fs::read_to_string(path)?;
+            urls.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+
+        Ok(urls)
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AuthPw {
+    /// Password for basic auth, given directly on the command line
+    #[arg(long)]
+    pub auth_pw_plain: Option<String>,
+
+    /// Password for basic auth, looked up in the Checkmk password store
+    #[arg(long)]
+    pub auth_pwstore: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Options,
+    Trace,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum OnRedirect {
+    Ok,
+    Warn,
+    Crit,
+    Unknown,
+    Follow,
+    Sticky,
+    Stickyport,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum ForceIP {
+    Ipv4,
+    Ipv6,
+}
+
+fn parse_status_set(s: &str) -> Result<HashSet<u16>, String> {
+    let mut codes = HashSet::new();
+    for part in s.split(',') {
+        let part = part.trim();
+        if let Some((start, end)) = part.split_once("..=") {
+            let start: u16 = start
+                .trim()
+                .parse()
+                .map_err(|e: std::num::ParseIntError| e.to_string())?;
+            let end: u16 = end
+                .trim()
+                .parse()
+                .map_err(|e: std::num::ParseIntError| e.to_string())?;
+            codes.extend(start..=end);
+        } else {
+            codes.insert(part.parse::<u16>().map_err(|e| e.to_string())?);
+        }
+    }
+    Ok(codes)
+}
+
+fn parse_levels(s: &str) -> Result<(usize, Option<usize>), String> {
+    let mut parts = s.splitn(2, ',');
+    let lower = parts
+        .next()
+        .ok_or_else(|| "missing lower bound".to_string())?
+        .parse::<usize>()
+        .map_err(|e| e.to_string())?;
+    let upper = match parts.next() {
+        Some(v) => Some(v.parse::<usize>().map_err(|e| e.to_string())?),
+        None => None,
+    };
+    Ok((lower, upper))
+}
+
+fn parse_u64_levels(s: &str) -> Result<(u64, Option<u64>), String> {
+    let mut parts = s.splitn(2, ',');
+    let warn = parts
+        .next()
+        .ok_or_else(|| "missing warn level".to_string())?
+        .parse::<u64>()
+        .map_err(|e| e.to_string())?;
+    let crit = match parts.next() {
+        Some(v) => Some(v.parse::<u64>().map_err(|e| e.to_string())?),
+        None => None,
+    };
+    Ok((warn, crit))
+}
+
+fn parse_float_levels(s: &str) -> Result<(f64, Option<f64>), String> {
+    let mut parts = s.splitn(2, ',');
+    let warn = parts
+        .next()
+        .ok_or_else(|| "missing warn level".to_string())?
+        .parse::<f64>()
+        .map_err(|e| e.to_string())?;
+    let crit = match parts.next() {
+        Some(v) => Some(v.parse::<f64>().map_err(|e| e.to_string())?),
+        None => None,
+    };
+    Ok((warn, crit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status_set_single_codes() {
+        let codes = parse_status_set("200,301,429").unwrap();
+        assert_eq!(codes, HashSet::from([200, 301, 429]));
+    }
+
+    #[test]
+    fn parse_status_set_range() {
+        let codes = parse_status_set("200..=204").unwrap();
+        assert_eq!(codes, HashSet::from([200, 201, 202, 203, 204]));
+    }
+
+    #[test]
+    fn parse_status_set_mixed_ranges_and_codes() {
+        let codes = parse_status_set("200..=204,301,429").unwrap();
+        assert_eq!(codes, HashSet::from([200, 201, 202, 203, 204, 301, 429]));
+    }
+
+    #[test]
+    fn parse_status_set_trims_whitespace() {
+        let codes = parse_status_set(" 200 , 301 ").unwrap();
+        assert_eq!(codes, HashSet::from([200, 301]));
+    }
+
+    #[test]
+    fn parse_status_set_rejects_malformed_range() {
+        assert!(parse_status_set("200..=").is_err());
+        assert!(parse_status_set("..=204").is_err());
+    }
+
+    #[test]
+    fn parse_status_set_rejects_non_numeric() {
+        assert!(parse_status_set("not-a-code").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (C) 2023 Checkmk GmbH - License: GNU General Public License v2
+// This file is part of Checkmk (https://checkmk.com). It is subject to the terms and
+// conditions defined in the file COPYING, which is part of this source code package.
+
+use crate::cli::{ForceIP, Method, OnRedirect};
+use reqwest::header::HeaderMap;
+use reqwest::redirect::Policy;
+use rustls::pki_types::{CertificateDer, ServerName};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+pub struct Request {
+    client: reqwest::Client,
+    request: reqwest::Request,
+}
+
+impl Request {
+    /// Clones the request so it can be re-issued on retry. Returns `None` if the request
+    /// body is a non-replayable stream.
+    pub fn try_clone(&self) -> Option<Request> {
+        Some(Request {
+            client: self.client.clone(),
+            request: self.request.try_clone()?,
+        })
+    }
+}
+
+pub struct Response {
+    pub status: reqwest::StatusCode,
+    pub version: reqwest::Version,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+}
+
+/// Builds the `reqwest::Client` a request will be issued through. Split out of
+/// `prepare_request` so batch mode can build one client up front and share it (and its
+/// connection pool) across every URL instead of paying for a fresh one per check.
+pub fn build_client(
+    timeout: u64,
+    onredirect: OnRedirect,
+    max_redirs: u8,
+    force_ip_version: Option<ForceIP>,
+) -> Result<reqwest::Client, reqwest::Error> {
+    let mut client_builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .redirect(match onredirect {
+            OnRedirect::Follow | OnRedirect::Sticky | OnRedirect::Stickyport => {
+                Policy::limited(max_redirs as usize)
+            }
+            _ => Policy::none(),
+        });
+
+    client_builder = match force_ip_version {
+        Some(ForceIP::Ipv4) => client_builder.local_address(Some("0.0.0.0".parse().unwrap())),
+        Some(ForceIP::Ipv6) => client_builder.local_address(Some("::".parse().unwrap())),
+        None => client_builder,
+    };
+
+    client_builder.build()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_request(
+    client: reqwest::Client,
+    url: String,
+    method: Method,
+    user_agent: Option<String>,
+    headers: Vec<String>,
+    auth_user: Option<String>,
+    auth_pw: Option<String>,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+) -> Result<Request, reqwest::Error> {
+    let mut request_builder = client.request(
+        match method {
+            Method::Get => reqwest::Method::GET,
+            Method::Head => reqwest::Method::HEAD,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+            Method::Options => reqwest::Method::OPTIONS,
+            Method::Trace => reqwest::Method::TRACE,
+        },
+        url,
+    );
+
+    if let Some(user_agent) = user_agent {
+        request_builder = request_builder.header("User-Agent", user_agent);
+    }
+
+    for header in headers {
+        if let Some((name, value)) = header.split_once(':') {
+            request_builder = request_builder.header(name.trim(), value.trim());
+        }
+    }
+
+    if let Some(user) = auth_user {
+        request_builder = request_builder.basic_auth(user, auth_pw);
+    }
+
+    if let Some(etag) = if_none_match {
+        request_builder = request_builder.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+
+    if let Some(date) = if_modified_since {
+        request_builder = request_builder.header(reqwest::header::IF_MODIFIED_SINCE, date);
+    }
+
+    let request = request_builder.build()?;
+
+    Ok(Request { client, request })
+}
+
+pub async fn perform_request(
+    request: Request,
+    without_body: bool,
+) -> Result<Response, reqwest::Error> {
+    let response = request.client.execute(request.request).await?;
+
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+
+    let body = if without_body {
+        None
+    } else {
+        Some(response.text().await?)
+    };
+
+    Ok(Response {
+        status,
+        version,
+        headers,
+        body,
+    })
+}
+
+/// Opens a dedicated TLS connection to `host:port` and returns the peer's leaf certificate,
+/// independent of whatever request the main check is performing. Both the TCP connect and
+/// the TLS handshake are bounded by `timeout`, so a non-responding host can't hang the check.
+pub async fn fetch_peer_certificate(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<CertificateDer<'static>, std::io::Error> {
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = rustls::ClientConfig::builder()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let domain = ServerName::try_from(host.to_owned())
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid DNS name"))?;
+
+    let stream = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "connect timed out"))??;
+    let tls_stream = tokio::time::timeout(timeout, connector.connect(domain, stream))
+        .await
+        .map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "TLS handshake timed out")
+        })??;
+
+    let (_, session) = tls_stream.get_ref();
+    session
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .cloned()
+        .map(|cert| cert.into_owned())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no peer certificate presented",
+            )
+        })
+}
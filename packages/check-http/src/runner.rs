@@ -8,20 +8,50 @@ use std::time::Instant;
 use crate::checking;
 use crate::checking::{Bounds, CheckResult, Limits, State};
 use crate::http;
+use regex::Regex;
 use std::time::Duration;
 
-pub async fn collect_checks(args: Cli) -> Vec<CheckResult> {
+/// Runs every configured check against a single URL, sharing the rest of `args` (method,
+/// headers, thresholds, ...) and `client` (so its connection pool is reused) across however
+/// many URLs the caller drives this with.
+pub async fn collect_checks(url: String, args: &Cli, client: &reqwest::Client) -> Vec<CheckResult> {
+    if let Some((warn, crit)) = args.certificate {
+        return check_certificate_only(&url, warn, crit, args.timeout).await;
+    }
+
+    if args.without_body && (args.string.is_some() || args.regex.is_some()) {
+        return vec![CheckResult::new(
+            State::Unknown,
+            "--string/--regex require the response body; remove --without-body".to_string(),
+        )];
+    }
+
+    let regex = match args.regex.as_deref().map(Regex::new) {
+        Some(Ok(re)) => Some(re),
+        Some(Err(err)) => {
+            return vec![CheckResult::new(
+                State::Unknown,
+                format!("Invalid --regex: {err}"),
+            )]
+        }
+        None => None,
+    };
+
+    let conditional = args.if_none_match.is_some() || args.if_modified_since.is_some();
+
     let Ok(request) = http::prepare_request(
-        args.url,
-        args.method,
-        args.user_agent,
-        args.headers,
-        args.timeout,
-        args.auth_user,
-        args.auth_pw.auth_pw_plain.or(args.auth_pw.auth_pwstore),
-        args.onredirect.clone(),
-        args.max_redirs,
-        args.force_ip_version,
+        client.clone(),
+        url,
+        args.method.clone(),
+        args.user_agent.clone(),
+        args.headers.clone(),
+        args.auth_user.clone(),
+        args.auth_pw
+            .auth_pw_plain
+            .clone()
+            .or(args.auth_pw.auth_pwstore.clone()),
+        args.if_none_match.clone(),
+        args.if_modified_since.clone(),
     ) else {
         return vec![CheckResult {
             state: State::Unknown,
@@ -29,19 +59,57 @@ pub async fn collect_checks(args: Cli) -> Vec<CheckResult> {
         }];
     };
 
-    let now = Instant::now();
-    let response = match http::perform_request(request, args.without_body).await {
+    let mut attempts = 0;
+    let (response, attempts, now) = loop {
+        attempts += 1;
+        let Some(this_request) = request.try_clone() else {
+            return vec![CheckResult {
+                state: State::Unknown,
+                summary: "Error building the request".to_string(),
+            }];
+        };
+
+        // Re-armed on every attempt so retry-wait sleeps between attempts don't get
+        // counted as part of the reported response time.
+        let now = Instant::now();
+        match http::perform_request(this_request, args.without_body).await {
+            Ok(resp)
+                if resp.status.is_server_error()
+                    && attempts <= args.max_retries
+                    && !args
+                        .expected_status
+                        .as_ref()
+                        .is_some_and(|expected| expected.contains(&resp.status.as_u16())) =>
+            {
+                tokio::time::sleep(Duration::from_secs(args.retry_wait)).await;
+                continue;
+            }
+            Ok(resp) => break (Ok(resp), attempts, now),
+            Err(err) if is_retryable(&err) && attempts <= args.max_retries => {
+                tokio::time::sleep(Duration::from_secs(args.retry_wait)).await;
+                continue;
+            }
+            Err(err) => break (Err(err), attempts, now),
+        }
+    };
+
+    let response = match response {
         Ok(resp) => resp,
         Err(err) => {
+            let suffix = if attempts > 1 {
+                format!(" (after {attempts} attempts)")
+            } else {
+                String::new()
+            };
             if err.is_timeout() {
                 return vec![CheckResult {
                     state: State::Crit,
-                    summary: "timeout".to_string(),
+                    summary: format!("timeout{suffix}"),
                 }];
             } else if err.is_connect() {
                 return vec![CheckResult {
                     state: State::Crit,
-                    summary: "Failed to connect".to_string(),
+                    summary: format!("Failed to connect{suffix}"),
                 }];
             } else if err.is_redirect() {
                 return vec![CheckResult {
@@ -60,7 +128,19 @@ pub async fn collect_checks(args: Cli) -> Vec<CheckResult> {
     let elapsed = now.elapsed();
 
     vec![
-        checking::check_status(response.status, response.version, args.onredirect),
+        checking::check_status(
+            response.status,
+            response.version,
+            args.onredirect.clone(),
+            args.expected_status.as_ref(),
+            attempts,
+        ),
+        checking::check_content(
+            response.body.as_deref(),
+            args.string.as_deref(),
+            regex.as_ref(),
+            args.invert_regex,
+        ),
         checking::check_body(
             response.body,
             match args.page_size {
@@ -80,8 +160,60 @@ pub async fn collect_checks(args: Cli) -> Vec<CheckResult> {
             },
         ),
         checking::check_document_age(&response.headers, args.document_age_levels),
+        checking::check_cache(&response.headers, args.cache_max_age),
+        conditional
+            .then(|| checking::check_not_modified(response.status))
+            .flatten(),
     ]
     .into_iter()
     .flatten()
     .collect()
 }
+
+/// Runs only the TLS certificate expiry check, skipping status/body/response-time checks.
+/// Used when `--certificate` is given, since the user is explicitly asking to monitor
+/// certificate expiry rather than the endpoint's content.
+async fn check_certificate_only(
+    url: &str,
+    warn_days: u64,
+    crit_days: Option<u64>,
+    timeout: u64,
+) -> Vec<CheckResult> {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return vec![CheckResult::new(State::Unknown, "Invalid URL".to_string())];
+    };
+    if parsed.scheme() != "https" {
+        return vec![CheckResult::new(
+            State::Unknown,
+            "--certificate requires an https:// URL".to_string(),
+        )];
+    }
+    let Some(host) = parsed.host_str() else {
+        return vec![CheckResult::new(
+            State::Unknown,
+            "URL has no host".to_string(),
+        )];
+    };
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let levels = match crit_days {
+        Some(crit) => Limits::WarnCrit(warn_days as i64, crit as i64),
+        None => Limits::Warn(warn_days as i64),
+    };
+
+    match http::fetch_peer_certificate(host, port, Duration::from_secs(timeout)).await {
+        Ok(cert) => checking::check_certificate(cert.as_ref(), levels)
+            .into_iter()
+            .collect(),
+        Err(err) => vec![CheckResult::new(
+            State::Unknown,
+            format!("Error fetching certificate: {err}"),
+        )],
+    }
+}
+
+/// Whether a failed request is worth retrying. Timeouts and connection failures are
+/// transient; redirect-policy violations (e.g. hitting `max_redirs`) are not.
+fn is_retryable(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect()
+}